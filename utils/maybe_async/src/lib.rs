@@ -6,12 +6,195 @@
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::{
-    parse_macro_input, Expr, ImplItem, Item, ItemFn, ItemImpl, ItemTrait, TraitItem, TraitItemFn,
+    parse_macro_input, Block, Expr, GenericArgument, ImplItem, Item, ItemFn, ItemImpl, ItemTrait,
+    Path, PathArguments, ReturnType, Signature, Stmt, TraitItem, TraitItemFn, Type, TypeParamBound,
 };
 
+/// The definition of a tiny parking executor that drives a future to completion on the current
+/// thread, without pulling in an async runtime. Mirrors the `pollster` crate: the driving thread
+/// parks while the future is pending and its waker unparks that thread, so `poll` is only called
+/// again once the future has signalled it may be ready.
+///
+/// Emitted inline by both [`must_be_sync`] and [`test`], since a proc-macro crate can't export a
+/// plain function for its generated code to call.
+fn block_on_fn() -> proc_macro2::TokenStream {
+    quote! {
+        fn block_on<F: ::core::future::Future>(future: F) -> F::Output {
+            use ::core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+            use ::std::thread::{self, Thread};
+
+            unsafe fn clone(data: *const ()) -> RawWaker {
+                let thread = &*(data as *const Thread);
+                let cloned = Box::into_raw(Box::new(thread.clone()));
+                RawWaker::new(cloned as *const (), &VTABLE)
+            }
+            unsafe fn wake(data: *const ()) {
+                let thread = Box::from_raw(data as *mut Thread);
+                thread.unpark();
+            }
+            unsafe fn wake_by_ref(data: *const ()) {
+                let thread = &*(data as *const Thread);
+                thread.unpark();
+            }
+            unsafe fn drop_raw(data: *const ()) {
+                drop(Box::from_raw(data as *mut Thread));
+            }
+
+            static VTABLE: RawWakerVTable =
+                RawWakerVTable::new(clone, wake, wake_by_ref, drop_raw);
+
+            let mut future = ::core::pin::pin!(future);
+
+            let thread = Box::into_raw(Box::new(thread::current()));
+            let raw_waker = RawWaker::new(thread as *const (), &VTABLE);
+            let waker = unsafe { Waker::from_raw(raw_waker) };
+            let mut cx = Context::from_waker(&waker);
+
+            loop {
+                match future.as_mut().poll(&mut cx) {
+                    Poll::Ready(output) => return output,
+                    Poll::Pending => thread::park(),
+                }
+            }
+        }
+    }
+}
+
+/// Extracts `T` out of the first trait bound in `bounds` whose path ends in
+/// `Future<Output = T>`.
+fn future_output_from_bounds(
+    bounds: &syn::punctuated::Punctuated<TypeParamBound, syn::Token![+]>,
+) -> Option<Type> {
+    bounds.iter().find_map(|bound| {
+        let TypeParamBound::Trait(trait_bound) = bound else {
+            return None;
+        };
+        future_output_from_path(&trait_bound.path)
+    })
+}
+
+/// Extracts `T` out of a path ending in `Future<Output = T>`.
+fn future_output_from_path(path: &Path) -> Option<Type> {
+    let segment = path.segments.last()?;
+    if segment.ident != "Future" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    args.args.iter().find_map(|arg| match arg {
+        GenericArgument::AssocType(assoc) if assoc.ident == "Output" => Some(assoc.ty.clone()),
+        _ => None,
+    })
+}
+
+/// Unwraps the `Output` type of a desugared future return type, recognizing both
+/// `impl Future<Output = T>` and `Pin<Box<dyn Future<Output = T> + '_>>`.
+fn unwrap_future_output(ty: &Type) -> Option<Type> {
+    match ty {
+        Type::ImplTrait(impl_trait) => future_output_from_bounds(&impl_trait.bounds),
+        Type::Path(type_path) => {
+            let segment = type_path.path.segments.last()?;
+            if segment.ident != "Pin" {
+                return None;
+            }
+            let PathArguments::AngleBracketed(pin_args) = &segment.arguments else {
+                return None;
+            };
+            let GenericArgument::Type(Type::Path(box_path)) = pin_args.args.first()? else {
+                return None;
+            };
+            let box_segment = box_path.path.segments.last()?;
+            if box_segment.ident != "Box" {
+                return None;
+            }
+            let PathArguments::AngleBracketed(box_args) = &box_segment.arguments else {
+                return None;
+            };
+            let GenericArgument::Type(Type::TraitObject(trait_object)) = box_args.args.first()?
+            else {
+                return None;
+            };
+            future_output_from_bounds(&trait_object.bounds)
+        }
+        _ => None,
+    }
+}
+
+/// Rewrites a signature's `impl Future<Output = T>` / `Pin<Box<dyn Future<Output = T> + '_>>`
+/// return type to plain `-> T`, in place. Signatures with an ordinary return type are left
+/// untouched. Used when the `async` feature is off, so desugared future signatures collapse to
+/// synchronous ones just like an `async fn` does. Returns whether a rewrite happened.
+fn desugar_future_return(sig: &mut Signature) -> bool {
+    let ReturnType::Type(_, ty) = &sig.output else {
+        return false;
+    };
+    let Some(output) = unwrap_future_output(ty) else {
+        return false;
+    };
+    sig.output = ReturnType::Type(syn::token::RArrow::default(), Box::new(output));
+    true
+}
+
+/// Returns whether a signature already declares a desugared future return type, i.e.
+/// `impl Future<Output = T>` or `Pin<Box<dyn Future<Output = T> + '_>>`.
+fn is_future_return(sig: &Signature) -> bool {
+    let ReturnType::Type(_, ty) = &sig.output else {
+        return false;
+    };
+    unwrap_future_output(ty).is_some()
+}
+
+/// Given the block of a function whose desugared future return type was just collapsed to its
+/// `Output` by [`desugar_future_return`], unwraps the matching `async move { .. }` /
+/// `Box::pin(async move { .. })` tail expression so the block's value matches the new return
+/// type instead of still producing a future.
+///
+/// Functions with this return type shape are conventionally written with exactly that tail
+/// expression (it's the only way to construct the value outside of an `async fn`), so this
+/// covers the idiomatic case; anything else is left as-is for the author to reconcile.
+fn unwrap_async_body(block: &mut Block) {
+    let Some(Stmt::Expr(tail, None)) = block.stmts.last() else {
+        return;
+    };
+    // `Box::pin(async move { .. })`, for the `Pin<Box<dyn Future<Output = T>>>` case
+    if let Expr::Call(call) = tail {
+        if let Expr::Path(path) = &*call.func {
+            if path.path.is_ident("pin")
+                || path.path.segments.last().is_some_and(|s| s.ident == "pin")
+            {
+                if let Some(Expr::Async(inner)) = call.args.first() {
+                    let inner_stmts = inner.block.stmts.clone();
+                    block.stmts.splice(block.stmts.len() - 1.., inner_stmts);
+                    return;
+                }
+            }
+        }
+    }
+    // `async move { .. }`, for the `impl Future<Output = T>` case
+    if let Expr::Async(inner) = tail {
+        let inner_stmts = inner.block.stmts.clone();
+        block.stmts.splice(block.stmts.len() - 1.., inner_stmts);
+    }
+}
+
+/// Returns `true` if the generated `async_trait` wrapper should require the `Send` bound on the
+/// resulting futures, either because the `#[maybe_async(Send)]` argument was supplied or because
+/// the crate-wide `send` feature is enabled. Defaults to `false`, i.e. `?Send`.
+fn wants_send(attr: TokenStream) -> bool {
+    if cfg!(feature = "send") {
+        return true;
+    }
+    syn::parse::<syn::Ident>(attr).is_ok_and(|ident| ident == "Send")
+}
+
 /// Parses a function (regular or trait) and conditionally adds the `async` keyword depending on
 /// the `async` feature flag being enabled.
 ///
+/// By default, traits and impls are expanded with `#[async_trait::async_trait(?Send)]`. Pass
+/// `Send` as the attribute argument (`#[maybe_async(Send)]`), or enable the crate-wide `send`
+/// feature, to instead require `Send` futures (`#[async_trait::async_trait]`).
+///
 /// For example:
 /// ```ignore
 /// trait ExampleTrait {
@@ -43,31 +226,60 @@ use syn::{
 /// }
 /// ```
 #[proc_macro_attribute]
-pub fn maybe_async(_attr: TokenStream, input: TokenStream) -> TokenStream {
+pub fn maybe_async(attr: TokenStream, input: TokenStream) -> TokenStream {
+    let send = wants_send(attr);
+    let async_trait_attr = if send {
+        quote!(#[async_trait::async_trait])
+    } else {
+        quote!(#[async_trait::async_trait(?Send)])
+    };
+
     // Check if the input is a function
     if let Ok(func) = syn::parse::<ItemFn>(input.clone()) {
         if cfg!(feature = "async") {
             let ItemFn { attrs, vis, mut sig, block } = func;
-            sig.asyncness = Some(syn::token::Async::default());
+            // A signature that already returns a future explicitly doesn't need `async` added
+            // on top of it, or its body touched - it already behaves correctly as-is.
+            if !is_future_return(&sig) {
+                sig.asyncness = Some(syn::token::Async::default());
+            }
             quote! {
                 #(#attrs)* #vis #sig { #block }
             }
             .into()
         } else {
-            quote!(#func).into()
+            let ItemFn { attrs, vis, mut sig, mut block } = func;
+            if desugar_future_return(&mut sig) {
+                unwrap_async_body(&mut block);
+            }
+            quote! {
+                #(#attrs)* #vis #sig { #block }
+            }
+            .into()
         }
     }
     // Check if the input is a trait function
     else if let Ok(func) = syn::parse::<TraitItemFn>(input.clone()) {
         if cfg!(feature = "async") {
             let TraitItemFn { attrs, mut sig, default, semi_token } = func;
-            sig.asyncness = Some(syn::token::Async::default());
+            if !is_future_return(&sig) {
+                sig.asyncness = Some(syn::token::Async::default());
+            }
             quote! {
                 #(#attrs)* #sig #default #semi_token
             }
             .into()
         } else {
-            quote!(#func).into()
+            let TraitItemFn { attrs, mut sig, mut default, semi_token } = func;
+            if desugar_future_return(&mut sig) {
+                if let Some(block) = &mut default {
+                    unwrap_async_body(block);
+                }
+            }
+            quote! {
+                #(#attrs)* #sig #default #semi_token
+            }
+            .into()
         }
     }
     // Check if the input is a trait definition
@@ -77,21 +289,34 @@ pub fn maybe_async(_attr: TokenStream, input: TokenStream) -> TokenStream {
         let trait_generics = &trait_item.generics;
 
         if cfg!(feature = "async") {
-            // Modify each function in the trait to add async keyword
+            // Modify each function in the trait to add async keyword, unless it already
+            // returns a future explicitly
             trait_item.items.iter_mut().for_each(|item| {
                 if let TraitItem::Fn(method) = item {
-                    method.sig.asyncness = Some(syn::token::Async::default());
+                    if !is_future_return(&method.sig) {
+                        method.sig.asyncness = Some(syn::token::Async::default());
+                    }
                 }
             });
             let items = &trait_item.items;
             quote! {
-                #[async_trait::async_trait(?Send)]
+                #async_trait_attr
                 #vis trait #trait_ident #trait_generics {
                     #( #items )*
                 }
             }
             .into()
         } else {
+            // Collapse any desugared future return types to plain ones
+            trait_item.items.iter_mut().for_each(|item| {
+                if let TraitItem::Fn(method) = item {
+                    if desugar_future_return(&mut method.sig) {
+                        if let Some(block) = &mut method.default {
+                            unwrap_async_body(block);
+                        }
+                    }
+                }
+            });
             let items = &trait_item.items;
             quote! {
                 #vis trait #trait_ident #trait_generics {
@@ -107,10 +332,13 @@ pub fn maybe_async(_attr: TokenStream, input: TokenStream) -> TokenStream {
         let self_ty = &impl_item.self_ty;
 
         if cfg!(feature = "async") {
-            // Modify each function in the impl to add async keyword
+            // Modify each function in the impl to add async keyword, unless it already
+            // returns a future explicitly
             impl_item.items.iter_mut().for_each(|item| {
                 if let ImplItem::Fn(method) = item {
-                    method.sig.asyncness = Some(syn::token::Async::default());
+                    if !is_future_return(&method.sig) {
+                        method.sig.asyncness = Some(syn::token::Async::default());
+                    }
                 }
             });
 
@@ -119,7 +347,7 @@ pub fn maybe_async(_attr: TokenStream, input: TokenStream) -> TokenStream {
             if let Some((bang, trait_path, for_token)) = &impl_item.trait_ {
                 // Trait implementation
                 quote! {
-                    #[async_trait::async_trait(?Send)]
+                    #async_trait_attr
                     impl #impl_generics #bang #trait_path #for_token #self_ty {
                         #( #items )*
                     }
@@ -128,7 +356,7 @@ pub fn maybe_async(_attr: TokenStream, input: TokenStream) -> TokenStream {
             } else {
                 // Inherent impl block
                 quote! {
-                    #[async_trait::async_trait(?Send)]
+                    #async_trait_attr
                     impl #impl_generics #self_ty {
                         #( #items )*
                     }
@@ -136,7 +364,14 @@ pub fn maybe_async(_attr: TokenStream, input: TokenStream) -> TokenStream {
                 .into()
             }
         } else {
-            // No need to modify functions
+            // Collapse any desugared future return types to plain ones
+            impl_item.items.iter_mut().for_each(|item| {
+                if let ImplItem::Fn(method) = item {
+                    if desugar_future_return(&mut method.sig) {
+                        unwrap_async_body(&mut method.block);
+                    }
+                }
+            });
             quote!(#impl_item).into()
         }
     }
@@ -188,3 +423,272 @@ pub fn maybe_await(input: TokenStream) -> TokenStream {
 
     quote.into()
 }
+
+/// Parses a function (regular or trait) and unconditionally strips the `async` keyword,
+/// regardless of the `async` feature flag, while still correctly running any `.await` left in
+/// its body — including one produced by a nested `maybe_await!(..)` call, which expands
+/// independently of this attribute and always follows the crate-wide `async` feature rather than
+/// this item's own (now-sync) signature.
+///
+/// Useful for the rare helper that must stay synchronous even when the rest of the crate is
+/// compiled with the `async` feature enabled, e.g. a genuinely blocking routine that still needs
+/// to call into other `maybe_async`-annotated code.
+///
+/// This works by keeping the original body wrapped in an `async move` block and driving it to
+/// completion with a tiny built-in parking executor, so no heavy runtime dependency is pulled in
+/// just to stay synchronous.
+///
+/// ```ignore
+/// #[must_be_sync]
+/// async fn hello_world() {
+///     // Always expands without `async`, even if the `async` feature is on: the `.await` below
+///     // is driven to completion synchronously instead.
+///     some_future().await;
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn must_be_sync(_attr: TokenStream, input: TokenStream) -> TokenStream {
+    let block_on_def = block_on_fn();
+
+    // Check if the input is a function
+    if let Ok(func) = syn::parse::<ItemFn>(input.clone()) {
+        let ItemFn { attrs, vis, mut sig, block } = func;
+        sig.asyncness = None;
+        quote! {
+            #(#attrs)* #vis #sig {
+                #block_on_def
+                block_on(async move #block)
+            }
+        }
+        .into()
+    }
+    // Check if the input is a trait function
+    else if let Ok(func) = syn::parse::<TraitItemFn>(input.clone()) {
+        let TraitItemFn { attrs, mut sig, default, semi_token } = func;
+        sig.asyncness = None;
+        let default = default.map(|block| -> Block {
+            syn::parse_quote! {{
+                #block_on_def
+                block_on(async move #block)
+            }}
+        });
+        quote! {
+            #(#attrs)* #sig #default #semi_token
+        }
+        .into()
+    }
+    // Check if the input is a trait definition
+    else if let Ok(mut trait_item) = syn::parse::<ItemTrait>(input.clone()) {
+        let vis = &trait_item.vis;
+        let trait_ident = &trait_item.ident;
+        let trait_generics = &trait_item.generics;
+        trait_item.items.iter_mut().for_each(|item| {
+            if let TraitItem::Fn(method) = item {
+                method.sig.asyncness = None;
+                if let Some(block) = method.default.take() {
+                    method.default = Some(syn::parse_quote! {{
+                        #block_on_def
+                        block_on(async move #block)
+                    }});
+                }
+            }
+        });
+        let items = &trait_item.items;
+        quote! {
+            #vis trait #trait_ident #trait_generics {
+                #( #items )*
+            }
+        }
+        .into()
+    }
+    // Check if the input is an impl block
+    else if let Ok(mut impl_item) = syn::parse::<ItemImpl>(input.clone()) {
+        impl_item.items.iter_mut().for_each(|item| {
+            if let ImplItem::Fn(method) = item {
+                method.sig.asyncness = None;
+                let block = &method.block;
+                method.block = syn::parse_quote! {{
+                    #block_on_def
+                    block_on(async move #block)
+                }};
+            }
+        });
+        quote!(#impl_item).into()
+    }
+    // If none of the above matches, return the input unchanged
+    else {
+        input
+    }
+}
+
+/// Parses a function (regular or trait) and unconditionally adds the `async` keyword (and the
+/// `#[async_trait::async_trait(?Send)]` wrapper for traits/impls), regardless of the `async`
+/// feature flag.
+///
+/// Useful for the rare helper that must stay asynchronous even when the rest of the crate is
+/// compiled without the `async` feature, e.g. an I/O shim that must stay async.
+///
+/// ```ignore
+/// #[must_be_async]
+/// fn hello_world() {
+///     // Always expands with `async`, even if the `async` feature is off.
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn must_be_async(_attr: TokenStream, input: TokenStream) -> TokenStream {
+    // Check if the input is a function
+    if let Ok(func) = syn::parse::<ItemFn>(input.clone()) {
+        let ItemFn { attrs, vis, mut sig, block } = func;
+        sig.asyncness = Some(syn::token::Async::default());
+        quote! {
+            #(#attrs)* #vis #sig { #block }
+        }
+        .into()
+    }
+    // Check if the input is a trait function
+    else if let Ok(func) = syn::parse::<TraitItemFn>(input.clone()) {
+        let TraitItemFn { attrs, mut sig, default, semi_token } = func;
+        sig.asyncness = Some(syn::token::Async::default());
+        quote! {
+            #(#attrs)* #sig #default #semi_token
+        }
+        .into()
+    }
+    // Check if the input is a trait definition
+    else if let Ok(mut trait_item) = syn::parse::<ItemTrait>(input.clone()) {
+        let vis = &trait_item.vis;
+        let trait_ident = &trait_item.ident;
+        let trait_generics = &trait_item.generics;
+
+        // Modify each function in the trait to add async keyword
+        trait_item.items.iter_mut().for_each(|item| {
+            if let TraitItem::Fn(method) = item {
+                method.sig.asyncness = Some(syn::token::Async::default());
+            }
+        });
+        let items = &trait_item.items;
+        quote! {
+            #[async_trait::async_trait(?Send)]
+            #vis trait #trait_ident #trait_generics {
+                #( #items )*
+            }
+        }
+        .into()
+    }
+    // Check if the input is an impl block
+    else if let Ok(mut impl_item) = syn::parse::<ItemImpl>(input.clone()) {
+        let impl_generics = &impl_item.generics;
+        let self_ty = &impl_item.self_ty;
+
+        // Modify each function in the impl to add async keyword
+        impl_item.items.iter_mut().for_each(|item| {
+            if let ImplItem::Fn(method) = item {
+                method.sig.asyncness = Some(syn::token::Async::default());
+            }
+        });
+
+        let items = &impl_item.items;
+
+        if let Some((bang, trait_path, for_token)) = &impl_item.trait_ {
+            // Trait implementation
+            quote! {
+                #[async_trait::async_trait(?Send)]
+                impl #impl_generics #bang #trait_path #for_token #self_ty {
+                    #( #items )*
+                }
+            }
+            .into()
+        } else {
+            // Inherent impl block
+            quote! {
+                #[async_trait::async_trait(?Send)]
+                impl #impl_generics #self_ty {
+                    #( #items )*
+                }
+            }
+            .into()
+        }
+    }
+    // If none of the above matches, return the input unchanged
+    else {
+        input
+    }
+}
+
+/// Parses an arbitrary item and expands it verbatim only when the `async` feature is enabled;
+/// otherwise the item is deleted entirely.
+///
+/// Unlike [`maybe_async`], which unifies two structurally identical bodies by toggling the
+/// `async` keyword, `async_impl` is for the case where the sync and async versions of an API
+/// are genuinely different (e.g. one uses `rayon` parallel iterators and the other spawns
+/// tasks). Pair it with [`sync_impl`] on the sync counterpart so exactly one of the two is
+/// compiled in.
+///
+/// ```ignore
+/// #[async_impl]
+/// async fn compute(values: &[u32]) -> u32 {
+///     // ... spawns tasks ...
+/// }
+///
+/// #[sync_impl]
+/// fn compute(values: &[u32]) -> u32 {
+///     // ... uses rayon ...
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn async_impl(_attr: TokenStream, input: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(input as Item);
+    if cfg!(feature = "async") {
+        quote!(#item).into()
+    } else {
+        TokenStream::new()
+    }
+}
+
+/// Parses an arbitrary item and expands it verbatim only when the `async` feature is disabled;
+/// otherwise the item is deleted entirely.
+///
+/// The sync counterpart to [`async_impl`]; see its documentation for when to reach for this
+/// pair of attributes instead of [`maybe_async`].
+#[proc_macro_attribute]
+pub fn sync_impl(_attr: TokenStream, input: TokenStream) -> TokenStream {
+    let item = parse_macro_input!(input as Item);
+    if cfg!(feature = "async") {
+        TokenStream::new()
+    } else {
+        quote!(#item).into()
+    }
+}
+
+/// Parses a test function and emits one test body that runs under both the sync and async
+/// configurations of the crate.
+///
+/// Regardless of the `async` feature flag, `async` is stripped from the signature and the body
+/// is driven to completion on the same built-in parking executor [`must_be_sync`] uses, so the
+/// test runs as a plain `#[test]` on both configurations without pulling in a runtime dependency
+/// (such as `tokio`) just for the test harness.
+///
+/// ```ignore
+/// #[maybe_async::test]
+/// async fn it_works() {
+///     let w = maybe_await!(world());
+///     assert_eq!(w, "world");
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn test(_attr: TokenStream, input: TokenStream) -> TokenStream {
+    let func = parse_macro_input!(input as ItemFn);
+    let ItemFn { attrs, vis, mut sig, block } = func;
+    sig.asyncness = None;
+    let block_on_def = block_on_fn();
+
+    quote! {
+        #(#attrs)*
+        #[test]
+        #vis #sig {
+            #block_on_def
+            block_on(async move #block)
+        }
+    }
+    .into()
+}
@@ -0,0 +1,27 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use winter_maybe_async::{async_impl, sync_impl};
+
+// If either attribute failed to delete its own item in the mode it does not belong to, this file
+// would fail to compile with a duplicate-definition error, since both variants share the same name.
+#[async_impl]
+fn compute(values: &[u32]) -> u32 {
+    values.iter().sum::<u32>() + 1
+}
+
+#[sync_impl]
+fn compute(values: &[u32]) -> u32 {
+    values.iter().sum::<u32>() + 2
+}
+
+#[test]
+fn exactly_one_variant_is_compiled_in() {
+    #[cfg(feature = "async")]
+    assert_eq!(compute(&[1, 2, 3]), 7);
+
+    #[cfg(not(feature = "async"))]
+    assert_eq!(compute(&[1, 2, 3]), 8);
+}
@@ -0,0 +1,17 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use winter_maybe_async::{maybe_async, maybe_await};
+
+#[maybe_async]
+fn world() -> String {
+    "world".to_string()
+}
+
+#[winter_maybe_async::test]
+async fn it_works() {
+    let w = maybe_await!(world());
+    assert_eq!(w, "world");
+}
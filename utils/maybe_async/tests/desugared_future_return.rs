@@ -0,0 +1,45 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// Only referenced when the `async` feature is on, where `maybe_async` leaves these return types
+// alone.
+#[allow(unused_imports)]
+use std::future::Future;
+#[allow(unused_imports)]
+use std::pin::Pin;
+
+use winter_maybe_async::{maybe_async, maybe_await};
+
+struct Adder {
+    base: u32,
+}
+
+impl Adder {
+    // A multi-statement body: the `let base = ..;` above the `Box::pin(async move { .. })` tail
+    // must survive the sync-mode desugaring, not just the tail expression itself.
+    #[maybe_async]
+    fn compute(&self, extra: u32) -> Pin<Box<dyn Future<Output = u32> + '_>> {
+        let base = self.base;
+        Box::pin(async move { base + extra })
+    }
+}
+
+#[maybe_async]
+fn compute_impl_future(base: u32, extra: u32) -> impl Future<Output = u32> {
+    let total = base + extra;
+    async move { total }
+}
+
+#[winter_maybe_async::test]
+async fn boxed_future_desugars_with_leading_statements_intact() {
+    let result = maybe_await!(Adder { base: 10 }.compute(32));
+    assert_eq!(result, 42);
+}
+
+#[winter_maybe_async::test]
+async fn impl_future_desugars_with_leading_statements_intact() {
+    let result = maybe_await!(compute_impl_future(10, 32));
+    assert_eq!(result, 42);
+}
@@ -0,0 +1,64 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+// The `Send` bound on the `async_trait`-generated futures only has an observable effect once
+// `maybe_async` actually adds `async`, i.e. when the crate-wide `async` feature is on.
+#![cfg(feature = "async")]
+
+use std::rc::Rc;
+
+use winter_maybe_async::{maybe_async, maybe_await};
+
+fn assert_send<T: Send>(_: &T) {}
+
+#[maybe_async]
+fn label() -> String {
+    "hello".to_string()
+}
+
+// Default (no argument): the generated trait method keeps the `?Send` bound, so a future that
+// holds a non-`Send` value (like `Rc`) across an `.await` point still compiles.
+#[maybe_async]
+trait Greeter {
+    fn greet(&self) -> String;
+}
+
+struct DefaultGreeter;
+
+#[maybe_async]
+impl Greeter for DefaultGreeter {
+    fn greet(&self) -> String {
+        let prefix = Rc::new("hello, ".to_string());
+        let who = maybe_await!(label());
+        format!("{prefix}{who}")
+    }
+}
+
+// `#[maybe_async(Send)]`: the generated trait method requires `Send` futures instead.
+#[maybe_async(Send)]
+trait SendGreeter {
+    fn greet(&self) -> String;
+}
+
+struct SendGreeterImpl;
+
+#[maybe_async(Send)]
+impl SendGreeter for SendGreeterImpl {
+    fn greet(&self) -> String {
+        maybe_await!(label())
+    }
+}
+
+#[winter_maybe_async::test]
+async fn default_bound_allows_non_send_futures() {
+    assert_eq!(DefaultGreeter.greet().await, "hello, hello");
+}
+
+#[winter_maybe_async::test]
+async fn send_argument_requires_send_futures() {
+    let future = SendGreeterImpl.greet();
+    assert_send(&future);
+    assert_eq!(future.await, "hello");
+}
@@ -0,0 +1,24 @@
+// Copyright (c) Facebook, Inc. and its affiliates.
+//
+// This source code is licensed under the MIT license found in the
+// LICENSE file in the root directory of this source tree.
+
+use winter_maybe_async::{maybe_async, maybe_await, must_be_sync};
+
+#[maybe_async]
+fn world() -> String {
+    "world".to_string()
+}
+
+// `greet` must stay synchronous even when the crate-wide `async` feature is enabled, so it calls
+// into `world` through `maybe_await!` and lets `must_be_sync` drive the result to completion.
+#[must_be_sync]
+async fn greet() -> String {
+    let who = maybe_await!(world());
+    format!("hello {who}")
+}
+
+#[test]
+fn must_be_sync_drives_maybe_await_to_completion() {
+    assert_eq!(greet(), "hello world");
+}